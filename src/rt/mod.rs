@@ -13,8 +13,11 @@ mod io;
 pub use self::io::{Read, ReadBuf, ReadBufCursor, Write};
 
 use std::{
+    error::Error,
+    fmt,
     future::Future,
     pin::Pin,
+    task::{Context, Poll},
     time::{Duration, Instant},
 };
 
@@ -45,19 +48,222 @@ pub trait Executor<Fut> {
     fn execute(&self, fut: Fut);
 }
 
+/// An [`Executor`] that can also hand back a handle to the spawned future.
+///
+/// This is an opt-in companion to [`Executor`]: implement it when your runtime
+/// can give back something like tokio's `JoinHandle`, so that a spawned future
+/// can be awaited for its output, or cancelled by dropping the handle. Hyper
+/// itself does not call `spawn`: its own connection-driving tasks are still
+/// started with `execute` and detached.
+///
+/// # Example
+///
+/// ```
+/// # use hyper::rt::{Executor, JoinError, SpawnHandle};
+/// # use std::future::Future;
+/// # use std::pin::Pin;
+/// # use std::task::{Context, Poll};
+/// #[derive(Clone)]
+/// struct TokioExecutor;
+///
+/// impl<F> Executor<F> for TokioExecutor
+/// where
+///     F: Future + Send + 'static,
+///     F::Output: Send + 'static,
+/// {
+///     fn execute(&self, future: F) {
+///         tokio::spawn(future);
+///     }
+/// }
+///
+/// // Adapts tokio's `JoinHandle`, whose error type is tokio's own
+/// // `JoinError`, to resolve with `hyper::rt::JoinError` instead. A bare
+/// // `tokio::task::JoinHandle` *detaches* its task on drop, so this wrapper
+/// // aborts it instead, to honor `SpawnHandle`'s cancel-on-drop contract.
+/// struct TokioJoinHandle<T>(tokio::task::JoinHandle<T>);
+///
+/// impl<T> Future for TokioJoinHandle<T> {
+///     type Output = Result<T, JoinError>;
+///
+///     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+///         Pin::new(&mut self.0).poll(cx).map(|res| {
+///             res.map_err(|err| {
+///                 if err.is_cancelled() {
+///                     JoinError::cancelled()
+///                 } else {
+///                     JoinError::panicked()
+///                 }
+///             })
+///         })
+///     }
+/// }
+///
+/// impl<T> Drop for TokioJoinHandle<T> {
+///     fn drop(&mut self) {
+///         self.0.abort();
+///     }
+/// }
+///
+/// impl<F> SpawnHandle<F> for TokioExecutor
+/// where
+///     F: Future + Send + 'static,
+///     F::Output: Send + 'static,
+/// {
+///     type JoinHandle = TokioJoinHandle<F::Output>;
+///
+///     fn spawn(&self, future: F) -> Self::JoinHandle {
+///         TokioJoinHandle(tokio::spawn(future))
+///     }
+/// }
+/// ```
+pub trait SpawnHandle<Fut>: Executor<Fut>
+where
+    Fut: Future,
+{
+    /// A future resolving to the spawned future's output.
+    ///
+    /// Dropping this handle should signal the executor to cancel the spawned
+    /// future, so that graceful-shutdown paths can abort in-flight tasks.
+    type JoinHandle: Future<Output = Result<Fut::Output, JoinError>> + Send;
+
+    /// Place the future into the executor to be run, returning a handle to it.
+    fn spawn(&self, fut: Fut) -> Self::JoinHandle;
+}
+
+/// An error returned when awaiting a [`SpawnHandle::JoinHandle`] fails because
+/// the spawned future panicked or was cancelled.
+#[derive(Debug)]
+pub struct JoinError {
+    is_cancelled: bool,
+}
+
+impl JoinError {
+    /// Create a `JoinError` signaling that the spawned future panicked.
+    pub fn panicked() -> Self {
+        JoinError {
+            is_cancelled: false,
+        }
+    }
+
+    /// Create a `JoinError` signaling that the spawned future was cancelled.
+    pub fn cancelled() -> Self {
+        JoinError { is_cancelled: true }
+    }
+
+    /// Returns `true` if the task was cancelled rather than panicking.
+    pub fn is_cancelled(&self) -> bool {
+        self.is_cancelled
+    }
+}
+
+impl fmt::Display for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_cancelled {
+            f.write_str("task was cancelled")
+        } else {
+            f.write_str("task panicked")
+        }
+    }
+}
+
+impl Error for JoinError {}
+
 /// A timer which provides timer-like functions.
+///
+/// The `Instant` associated type is the timer's own notion of "a point in
+/// time", kept abstract so a `Timer` can be backed by something other than
+/// `std::time::Instant` (for example, a monotonic clock sourced from the
+/// browser on `wasm32-unknown-unknown`, or a simulated clock in tests). A
+/// `std`-backed implementation can simply set `type Instant =
+/// std::time::Instant;` and get the usual behavior.
+///
+/// Note this is a breaking change for existing `Timer` implementors: stable
+/// Rust has no associated-type defaults, so every impl must now add `type
+/// Instant`, `now`, `duration_between`, and `interval` alongside the
+/// pre-existing methods.
 pub trait Timer {
+    /// The runtime's notion of a point in time.
+    type Instant: Copy + Send + Sync + Unpin;
+
     /// Return a future that resolves in `duration` time.
-    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Sleep>>;
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Sleep<Self::Instant>>>;
 
     /// Return a future that resolves at `deadline`.
-    fn sleep_until(&self, deadline: Instant) -> Pin<Box<dyn Sleep>>;
+    fn sleep_until(&self, deadline: Self::Instant) -> Pin<Box<dyn Sleep<Self::Instant>>>;
 
     /// Reset a future to resolve at `new_deadline` instead.
-    fn reset(&self, sleep: &mut Pin<Box<dyn Sleep>>, new_deadline: Instant) {
-        *sleep = self.sleep_until(new_deadline);
+    ///
+    /// Tries [`Sleep::reset`] first, to reschedule in place without dropping
+    /// the waker already registered with the original future. Only falls
+    /// back to reallocating via `sleep_until` if the `Sleep` reports it
+    /// doesn't support in-place rescheduling.
+    fn reset(&self, sleep: &mut Pin<Box<dyn Sleep<Self::Instant>>>, new_deadline: Self::Instant) {
+        if !sleep.as_mut().reset(new_deadline) {
+            *sleep = self.sleep_until(new_deadline);
+        }
+    }
+
+    /// Return the current instant, as measured by this timer's clock.
+    fn now(&self) -> Self::Instant;
+
+    /// Returns the amount of time elapsed since `earlier`.
+    fn elapsed(&self, earlier: Self::Instant) -> Duration {
+        self.duration_between(earlier, self.now())
+    }
+
+    /// Returns the amount of time between `earlier` and `later`.
+    ///
+    /// # Panics
+    ///
+    /// Like [`std::time::Instant::duration_since`], panics if `later` is
+    /// earlier than `earlier`, i.e. if `later - earlier` would be negative.
+    fn duration_between(&self, earlier: Self::Instant, later: Self::Instant) -> Duration;
+
+    /// Return a ticker that fires repeatedly every `period`.
+    ///
+    /// Ticks fire at the fixed points `start + n * period`, rather than
+    /// `last_fire + period`, so latency accumulated while the ticker wasn't
+    /// polled doesn't make it drift. This is meant for keep-alive pings and
+    /// connection-health probes, which otherwise have to be rebuilt by hand
+    /// out of repeated `sleep_until`/`reset` calls.
+    fn interval(&self, period: Duration) -> Pin<Box<dyn Interval<Self::Instant>>>;
+}
+
+/// A future returned by a `Timer`, which resolves to the instant it fired at.
+///
+/// The `I` parameter is the `Timer`'s [`Timer::Instant`] type; it defaults to
+/// `std::time::Instant` so `dyn Sleep` keeps working for `std`-backed timers
+/// without spelling it out everywhere.
+pub trait Sleep<I = Instant>: Send + Sync + Future<Output = I> {
+    /// Reschedule this sleep in place to fire at `new_deadline`, instead of
+    /// being dropped and replaced by a freshly allocated future.
+    ///
+    /// Rescheduling in place preserves any waker already registered with
+    /// this future, so a task polling it doesn't need to be polled again
+    /// just to re-register. Returns `true` if the reschedule happened in
+    /// place, or `false` if this `Sleep` doesn't support it, in which case
+    /// [`Timer::reset`] falls back to reallocating.
+    fn reset(self: Pin<&mut Self>, new_deadline: I) -> bool {
+        let _ = new_deadline;
+        false
     }
 }
 
-/// A future returned by a `Timer`.
-pub trait Sleep: Send + Sync + Future<Output = ()> {}
+/// A ticker returned by [`Timer::interval`], firing once per period.
+///
+/// The `I` parameter is the `Timer`'s [`Timer::Instant`] type; it defaults to
+/// `std::time::Instant` so `dyn Interval` keeps working for `std`-backed
+/// timers without spelling it out everywhere.
+pub trait Interval<I = Instant>: Send + Sync {
+    /// Poll for the next tick, resolving at the next fixed point
+    /// `start + n * period`, where `start` is when the interval was created
+    /// (or last [`reset`](Interval::reset)). This is an absolute schedule,
+    /// not `last_fire + period`, so latency in polling one tick doesn't push
+    /// later ticks back and the interval doesn't drift.
+    fn poll_tick(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<I>;
+
+    /// Rearm the interval so its next tick fires one `period` from now,
+    /// without dropping any waker already registered by a task polling this
+    /// interval, and without reallocating.
+    fn reset(self: Pin<&mut Self>);
+}